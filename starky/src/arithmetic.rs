@@ -0,0 +1,212 @@
+//! Helpers for building the "add with carry" / "compare with borrow" constraints that show up in
+//! EVM-style 32-bit-limb arithmetic STARKs (ADD, SUB, LT, GT, ...), built on top of
+//! [`crate::constraint_consumer::ConstraintConsumer`] and
+//! [`crate::constraint_consumer::RecursiveConstraintConsumer`]. Rather than hand-writing the limb
+//! carry chain (and its common off-by-one bugs) for every such STARK, callers split their values
+//! into limbs and call one of these.
+
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::Field;
+use plonky2::field::packed_field::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+
+/// Constrains `x + y = z + carry_out * base^num_limbs`, where `x`, `y`, `z` are given as
+/// `num_limbs` limbs (least-significant first) in the given `base` (e.g. `1 << 16` for 16-bit
+/// limbs assembling a 32-bit word), and `limb_carries[i]` is the witnessed carry out of limb `i`
+/// (so `limb_carries.last()` is the overall carry-out, e.g. the CY column of an EVM-style ADD
+/// STARK). Each limb carry is constrained to be boolean.
+///
+/// The per-limb carries must be supplied by the caller (as trace columns) since a constraint
+/// cannot itself compute an integer carry; this only checks that the supplied carries are
+/// consistent with `x`, `y`, and `z`.
+pub fn add_with_carry<P: PackedField>(
+    consumer: &mut ConstraintConsumer<P>,
+    base: u64,
+    x_limbs: &[P],
+    y_limbs: &[P],
+    z_limbs: &[P],
+    limb_carries: &[P],
+) {
+    let num_limbs = x_limbs.len();
+    assert_eq!(y_limbs.len(), num_limbs);
+    assert_eq!(z_limbs.len(), num_limbs);
+    assert_eq!(limb_carries.len(), num_limbs);
+
+    let base = P::Scalar::from_canonical_u64(base);
+    let mut carry_in = P::ZEROS;
+    for i in 0..num_limbs {
+        let carry_out = limb_carries[i];
+        consumer.one(carry_out * (P::ONES - carry_out));
+        consumer.one(x_limbs[i] + y_limbs[i] + carry_in - z_limbs[i] - carry_out * base);
+        carry_in = carry_out;
+    }
+}
+
+/// Constrains `x - y = diff - borrow_out * base^num_limbs`, i.e. the subtraction-with-borrow
+/// dual of `add_with_carry`, given `num_limbs` limbs (least-significant first) in `base` and the
+/// witnessed per-limb borrows `limb_borrows`. Each limb borrow is constrained to be boolean.
+///
+/// The overall borrow (`limb_borrows.last()`) is exactly the LT flag: it is `1` iff `x < y`. For
+/// GT, combine it with the non-zero check on `diff_limbs` (GT holds iff the borrow is `0` and
+/// `diff_limbs` is not all zero); `diff_limbs` itself is the auxiliary "difference" output a
+/// STARK can expose alongside LT/GT.
+pub fn compare_with_borrow<P: PackedField>(
+    consumer: &mut ConstraintConsumer<P>,
+    base: u64,
+    x_limbs: &[P],
+    y_limbs: &[P],
+    diff_limbs: &[P],
+    limb_borrows: &[P],
+) {
+    let num_limbs = x_limbs.len();
+    assert_eq!(y_limbs.len(), num_limbs);
+    assert_eq!(diff_limbs.len(), num_limbs);
+    assert_eq!(limb_borrows.len(), num_limbs);
+
+    let base = P::Scalar::from_canonical_u64(base);
+    let mut borrow_in = P::ZEROS;
+    for i in 0..num_limbs {
+        let borrow_out = limb_borrows[i];
+        consumer.one(borrow_out * (P::ONES - borrow_out));
+        consumer.one(x_limbs[i] - y_limbs[i] - borrow_in - diff_limbs[i] + borrow_out * base);
+        borrow_in = borrow_out;
+    }
+}
+
+/// Recursive (in-circuit) analogue of `add_with_carry`.
+pub fn add_with_carry_recursive<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    consumer: &mut RecursiveConstraintConsumer<F, D>,
+    base: F,
+    x_limbs: &[ExtensionTarget<D>],
+    y_limbs: &[ExtensionTarget<D>],
+    z_limbs: &[ExtensionTarget<D>],
+    limb_carries: &[ExtensionTarget<D>],
+) {
+    let num_limbs = x_limbs.len();
+    assert_eq!(y_limbs.len(), num_limbs);
+    assert_eq!(z_limbs.len(), num_limbs);
+    assert_eq!(limb_carries.len(), num_limbs);
+
+    let one = builder.one_extension();
+    let mut carry_in = builder.zero_extension();
+    for i in 0..num_limbs {
+        let carry_out = limb_carries[i];
+        let one_minus_carry = builder.sub_extension(one, carry_out);
+        let booleanity = builder.mul_extension(carry_out, one_minus_carry);
+        consumer.one(builder, booleanity);
+
+        let sum = builder.add_extension(x_limbs[i], y_limbs[i]);
+        let sum = builder.add_extension(sum, carry_in);
+        let sum = builder.sub_extension(sum, z_limbs[i]);
+        let carry_term = builder.mul_const_extension(base, carry_out);
+        let residual = builder.sub_extension(sum, carry_term);
+        consumer.one(builder, residual);
+
+        carry_in = carry_out;
+    }
+}
+
+/// Recursive (in-circuit) analogue of `compare_with_borrow`.
+pub fn compare_with_borrow_recursive<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    consumer: &mut RecursiveConstraintConsumer<F, D>,
+    base: F,
+    x_limbs: &[ExtensionTarget<D>],
+    y_limbs: &[ExtensionTarget<D>],
+    diff_limbs: &[ExtensionTarget<D>],
+    limb_borrows: &[ExtensionTarget<D>],
+) {
+    let num_limbs = x_limbs.len();
+    assert_eq!(y_limbs.len(), num_limbs);
+    assert_eq!(diff_limbs.len(), num_limbs);
+    assert_eq!(limb_borrows.len(), num_limbs);
+
+    let one = builder.one_extension();
+    let mut borrow_in = builder.zero_extension();
+    for i in 0..num_limbs {
+        let borrow_out = limb_borrows[i];
+        let one_minus_borrow = builder.sub_extension(one, borrow_out);
+        let booleanity = builder.mul_extension(borrow_out, one_minus_borrow);
+        consumer.one(builder, booleanity);
+
+        let diff = builder.sub_extension(x_limbs[i], y_limbs[i]);
+        let diff = builder.sub_extension(diff, borrow_in);
+        let diff = builder.sub_extension(diff, diff_limbs[i]);
+        let borrow_term = builder.mul_const_extension(base, borrow_out);
+        let residual = builder.add_extension(diff, borrow_term);
+        consumer.one(builder, residual);
+
+        borrow_in = borrow_out;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    fn limbs_of(mut value: u64, base: u64, num_limbs: usize) -> Vec<F> {
+        let mut limbs = Vec::with_capacity(num_limbs);
+        for _ in 0..num_limbs {
+            limbs.push(F::from_canonical_u64(value % base));
+            value /= base;
+        }
+        limbs
+    }
+
+    #[test]
+    fn add_with_carry_accepts_a_true_addition() {
+        let base = 1 << 16;
+        let x = 50_000u64;
+        let y = 20_000u64;
+        let z = x + y; // limb 0 overflows (50_000 + 20_000 > 65_536) and carries into limb 1
+        let x_limbs = limbs_of(x, base, 2);
+        let y_limbs = limbs_of(y, base, 2);
+        let z_limbs = limbs_of(z, base, 2);
+        let limb_carries = vec![F::ONE, F::ZERO];
+
+        let mut consumer =
+            ConstraintConsumer::new(vec![F::from_canonical_u64(12345)], F::ONE, F::ONE, F::ONE);
+        add_with_carry(
+            &mut consumer,
+            base,
+            &x_limbs,
+            &y_limbs,
+            &z_limbs,
+            &limb_carries,
+        );
+        assert_eq!(consumer.accumulators(), vec![F::ZERO]);
+    }
+
+    #[test]
+    fn compare_with_borrow_flags_less_than() {
+        let base = 1 << 16;
+        let x = 10u64;
+        let y = 20u64;
+        let diff = x.wrapping_sub(y).wrapping_add(1 << 32);
+        let x_limbs = limbs_of(x, base, 2);
+        let y_limbs = limbs_of(y, base, 2);
+        let diff_limbs = limbs_of(diff, base, 2);
+        let limb_borrows = vec![F::ONE, F::ONE];
+
+        let mut consumer =
+            ConstraintConsumer::new(vec![F::from_canonical_u64(6789)], F::ONE, F::ONE, F::ONE);
+        compare_with_borrow(
+            &mut consumer,
+            base,
+            &x_limbs,
+            &y_limbs,
+            &diff_limbs,
+            &limb_borrows,
+        );
+        assert_eq!(consumer.accumulators(), vec![F::ZERO]);
+    }
+}
@@ -21,24 +21,38 @@ pub struct ConstraintConsumer<P: PackedField> {
     /// The evaluation of the Lagrange basis polynomial which is nonzero at the point associated
     /// with the last trace row, and zero at other points in the subgroup.
     lagrange_basis_last: P,
+
+    /// The evaluation, at the current point, of the vanishing polynomial of the last trace
+    /// point, i.e. `x - g^(n-1)` where `g` generates the trace subgroup of size `n`. This is
+    /// nonzero everywhere except at the last row, which makes it a filter for "transition"
+    /// constraints that relate row `i` to row `i+1` and must not be enforced across the
+    /// wrap-around from the last row back to the first.
+    z_last: P,
 }
 
 impl<P: PackedField> ConstraintConsumer<P> {
-    pub fn new(alphas: Vec<P::Scalar>, lagrange_basis_first: P, lagrange_basis_last: P) -> Self {
+    pub fn new(
+        alphas: Vec<P::Scalar>,
+        lagrange_basis_first: P,
+        lagrange_basis_last: P,
+        z_last: P,
+    ) -> Self {
         Self {
             constraint_accs: vec![P::ZEROS; alphas.len()],
             alphas,
             lagrange_basis_first,
             lagrange_basis_last,
+            z_last,
         }
     }
 
-    // TODO: Do this correctly.
-    pub fn accumulators(self) -> Vec<P::Scalar> {
+    /// Returns the accumulated constraint evaluations, one packed value per alpha. Each packed
+    /// value holds `P::WIDTH` scalars, one for every point of the coset this consumer was fed,
+    /// in the same lane order those points were packed in. Callers evaluating an LDE coset in
+    /// chunks (see [`crate::evaluator`]) should concatenate these in coset order to recover the
+    /// full quotient-polynomial evaluations.
+    pub fn accumulators(self) -> Vec<P> {
         self.constraint_accs
-            .into_iter()
-            .map(|acc| acc.as_slice()[0])
-            .collect()
     }
 
     /// Add one constraint.
@@ -67,6 +81,21 @@ impl<P: PackedField> ConstraintConsumer<P> {
     pub fn one_last_row(&mut self, constraint: P) {
         self.one(constraint * self.lagrange_basis_last);
     }
+
+    /// Add one constraint, but first multiply it by a filter such that it will only apply to
+    /// "transition" rows, i.e. every row but the last. This is the right filter for constraints
+    /// relating the current row to the next one, since the next row after the last is the first
+    /// row of the (cyclic) trace, and such constraints should not wrap around.
+    pub fn one_transition(&mut self, constraint: P) {
+        self.one(constraint * self.z_last);
+    }
+
+    /// Add a series of transition constraints. See `one_transition`.
+    pub fn many_transition(&mut self, constraints: impl IntoIterator<Item = P>) {
+        constraints
+            .into_iter()
+            .for_each(|constraint| self.one_transition(constraint));
+    }
 }
 
 pub struct RecursiveConstraintConsumer<F: RichField + Extendable<D>, const D: usize> {
@@ -84,10 +113,32 @@ pub struct RecursiveConstraintConsumer<F: RichField + Extendable<D>, const D: us
     /// with the last trace row, and zero at other points in the subgroup.
     lagrange_basis_last: ExtensionTarget<D>,
 
+    /// The evaluation, at the current point, of the vanishing polynomial of the last trace
+    /// point. See `ConstraintConsumer::z_last` for why this is the right filter for transition
+    /// constraints.
+    z_last: ExtensionTarget<D>,
+
     _phantom: PhantomData<F>,
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> RecursiveConstraintConsumer<F, D> {
+    pub fn new(
+        alpha: Target,
+        constraint_acc: ExtensionTarget<D>,
+        lagrange_basis_first: ExtensionTarget<D>,
+        lagrange_basis_last: ExtensionTarget<D>,
+        z_last: ExtensionTarget<D>,
+    ) -> Self {
+        Self {
+            alpha,
+            constraint_acc,
+            lagrange_basis_first,
+            lagrange_basis_last,
+            z_last,
+            _phantom: PhantomData,
+        }
+    }
+
     /// Add one constraint.
     pub fn one(&mut self, builder: &mut CircuitBuilder<F, D>, constraint: ExtensionTarget<D>) {
         self.constraint_acc =
@@ -126,4 +177,60 @@ impl<F: RichField + Extendable<D>, const D: usize> RecursiveConstraintConsumer<F
         let filtered_constraint = builder.mul_extension(constraint, self.lagrange_basis_last);
         self.one(builder, filtered_constraint);
     }
-}
\ No newline at end of file
+
+    /// Add one constraint, but first multiply it by a filter such that it will only apply to
+    /// "transition" rows, i.e. every row but the last. See `ConstraintConsumer::one_transition`.
+    pub fn one_transition(
+        &mut self,
+        builder: &mut CircuitBuilder<F, D>,
+        constraint: ExtensionTarget<D>,
+    ) {
+        let filtered_constraint = builder.mul_extension(constraint, self.z_last);
+        self.one(builder, filtered_constraint);
+    }
+
+    /// Add a series of transition constraints. See `one_transition`.
+    pub fn many_transition(
+        &mut self,
+        builder: &mut CircuitBuilder<F, D>,
+        constraints: impl IntoIterator<Item = ExtensionTarget<D>>,
+    ) {
+        constraints
+            .into_iter()
+            .for_each(|constraint| self.one_transition(builder, constraint));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::field_types::Field;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn one_transition_multiplies_by_z_last() {
+        let z_last = F::from_canonical_u64(9);
+        let mut consumer =
+            ConstraintConsumer::new(vec![F::from_canonical_u64(123)], F::ONE, F::ONE, z_last);
+        consumer.one_transition(F::from_canonical_u64(5));
+
+        assert_eq!(
+            consumer.accumulators(),
+            vec![F::from_canonical_u64(5) * z_last]
+        );
+    }
+
+    #[test]
+    fn one_transition_filters_out_the_wrap_around_row() {
+        let mut consumer =
+            ConstraintConsumer::new(vec![F::from_canonical_u64(123)], F::ONE, F::ONE, F::ZERO);
+        consumer.one_transition(F::from_canonical_u64(5));
+
+        // At the last row `z_last` vanishes, so a transition constraint is not enforced there no
+        // matter how far from satisfied the raw (unfiltered) constraint value is.
+        assert_eq!(consumer.accumulators(), vec![F::ZERO]);
+    }
+}
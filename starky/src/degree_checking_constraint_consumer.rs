@@ -0,0 +1,177 @@
+//! A symbolic stand-in for [`crate::constraint_consumer::ConstraintConsumer`] that tracks only
+//! degree bounds, so a STARK's `eval_packed_generic` can be run once against it (in tests or
+//! debug builds) to assert that every constraint it emits stays within the STARK's declared
+//! `constraint_degree`. This ports the `check_poly` idea from the Protostar gate builder: a
+//! gate/STARK whose true degree exceeds what the quotient-polynomial machinery assumes is a
+//! silent soundness bug, and this turns it into an immediate panic naming the offending
+//! constraint.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A symbolic value that tracks only an upper bound on a polynomial's total degree, not any
+/// actual evaluation. Arithmetic on `SymbolicDegree` follows the usual degree bounds:
+/// `deg(a + b) <= max(deg a, deg b)` and `deg(a * b) <= deg a + deg b`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SymbolicDegree(usize);
+
+impl SymbolicDegree {
+    /// The degree of a constant term.
+    pub const fn constant() -> Self {
+        Self(0)
+    }
+
+    /// The degree of a single trace wire (or any other degree-1 value, such as a Lagrange basis
+    /// or `z_last` filter).
+    pub const fn wire() -> Self {
+        Self(1)
+    }
+
+    /// An arbitrary degree bound, for constructing test fixtures or combining with gate-specific
+    /// knowledge that isn't expressible via `constant`/`wire` alone.
+    pub const fn new(degree: usize) -> Self {
+        Self(degree)
+    }
+
+    pub const fn degree(self) -> usize {
+        self.0
+    }
+}
+
+impl Add for SymbolicDegree {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.max(rhs.0))
+    }
+}
+
+impl Sub for SymbolicDegree {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + rhs
+    }
+}
+
+impl Neg for SymbolicDegree {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self
+    }
+}
+
+impl Mul for SymbolicDegree {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+/// Mirrors [`crate::constraint_consumer::ConstraintConsumer`]'s API, but instead of accumulating
+/// field evaluations, checks that every emitted constraint's degree stays within
+/// `constraint_degree`, panicking with the offending constraint's index otherwise.
+pub struct DegreeCheckingConstraintConsumer {
+    /// The STARK's declared bound on constraint degree; see `Stark::constraint_degree`.
+    constraint_degree: usize,
+
+    /// The degree of the first-row Lagrange basis filter (always 1: it is itself a degree-1
+    /// polynomial in the trace variable).
+    lagrange_basis_first: SymbolicDegree,
+
+    /// The degree of the last-row Lagrange basis filter.
+    lagrange_basis_last: SymbolicDegree,
+
+    /// The degree of the `z_last` transition filter.
+    z_last: SymbolicDegree,
+
+    /// The index of the next constraint to be recorded, used to identify the offending
+    /// constraint in panic messages.
+    next_index: usize,
+}
+
+impl DegreeCheckingConstraintConsumer {
+    pub fn new(constraint_degree: usize) -> Self {
+        Self {
+            constraint_degree,
+            lagrange_basis_first: SymbolicDegree::wire(),
+            lagrange_basis_last: SymbolicDegree::wire(),
+            z_last: SymbolicDegree::wire(),
+            next_index: 0,
+        }
+    }
+
+    fn record(&mut self, constraint: SymbolicDegree) {
+        let index = self.next_index;
+        self.next_index += 1;
+        assert!(
+            constraint.degree() <= self.constraint_degree,
+            "constraint {} has degree {}, which exceeds the STARK's declared constraint_degree of {}",
+            index,
+            constraint.degree(),
+            self.constraint_degree,
+        );
+    }
+
+    /// Check one constraint.
+    pub fn one(&mut self, constraint: SymbolicDegree) {
+        self.record(constraint);
+    }
+
+    /// Check a series of constraints.
+    pub fn many(&mut self, constraints: impl IntoIterator<Item = SymbolicDegree>) {
+        constraints
+            .into_iter()
+            .for_each(|constraint| self.one(constraint));
+    }
+
+    /// Check one constraint, as filtered to the first row of the trace.
+    pub fn one_first_row(&mut self, constraint: SymbolicDegree) {
+        self.record(constraint * self.lagrange_basis_first);
+    }
+
+    /// Check one constraint, as filtered to the last row of the trace.
+    pub fn one_last_row(&mut self, constraint: SymbolicDegree) {
+        self.record(constraint * self.lagrange_basis_last);
+    }
+
+    /// Check one transition constraint, as filtered to every row but the last. See
+    /// `ConstraintConsumer::one_transition`.
+    pub fn one_transition(&mut self, constraint: SymbolicDegree) {
+        self.record(constraint * self.z_last);
+    }
+
+    /// Check a series of transition constraints.
+    pub fn many_transition(&mut self, constraints: impl IntoIterator<Item = SymbolicDegree>) {
+        constraints
+            .into_iter()
+            .for_each(|constraint| self.one_transition(constraint));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_constraints_within_the_degree_bound() {
+        let mut consumer = DegreeCheckingConstraintConsumer::new(3);
+        // (wire * wire) * wire is degree 3, fine.
+        consumer.one(SymbolicDegree::wire() * SymbolicDegree::wire() * SymbolicDegree::wire());
+        // A transition-filtered degree-2 constraint becomes degree 3, still fine.
+        consumer.one_transition(SymbolicDegree::wire() * SymbolicDegree::wire());
+    }
+
+    #[test]
+    #[should_panic(expected = "constraint 0 has degree 4")]
+    fn rejects_constraints_over_the_degree_bound() {
+        let mut consumer = DegreeCheckingConstraintConsumer::new(3);
+        consumer.one(SymbolicDegree::new(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "constraint 1 has degree 4")]
+    fn reports_the_offending_constraint_index() {
+        let mut consumer = DegreeCheckingConstraintConsumer::new(3);
+        consumer.one(SymbolicDegree::new(2));
+        // A degree-3 constraint filtered to the last row becomes degree 4, over the bound.
+        consumer.one_last_row(SymbolicDegree::new(3));
+    }
+}
@@ -0,0 +1,128 @@
+use plonky2::field::packed_field::PackedField;
+use rayon::prelude::*;
+
+use crate::constraint_consumer::ConstraintConsumer;
+
+/// Picks a chunk size for splitting `num_points` packed evaluations of an LDE coset across
+/// rayon's thread pool. Mirrors the heuristic used by the halo2 constraint evaluator: start from
+/// four chunks per available thread, then recompute the chunk count from the rounded-up chunk
+/// size so the final chunk is never oversized relative to the rest. Without the second rounding
+/// step a `num_points` that is a poor fit for `num_chunks` can leave one thread with a
+/// disproportionately large tail chunk, stalling the whole evaluation on it.
+fn chunk_size(num_points: usize, num_threads: usize) -> usize {
+    let num_chunks = 4 * num_threads.max(1);
+    let chunk_size = (num_points + num_chunks - 1) / num_chunks;
+    chunk_size.max(1)
+}
+
+/// Evaluates a STARK's constraints over every packed point of an LDE coset in parallel.
+///
+/// `num_points` is the number of packed evaluation points in the coset (i.e. the coset size
+/// divided by `P::WIDTH`). For each one, `eval_point(i, consumer)` is expected to populate
+/// `consumer` with that point's constraint evaluations, via the usual `one`/`many`/`one_first_row`
+/// /`one_last_row` calls; a fresh `ConstraintConsumer` seeded with the same `alphas` is created
+/// per point so the per-point accumulators stay independent until they're combined below.
+///
+/// The coset is split into chunks sized by [`chunk_size`] and the chunks are evaluated across
+/// rayon's thread pool; within a chunk, points are evaluated sequentially. The per-point
+/// accumulators are then concatenated back together in coset order, so the returned
+/// `Vec<Vec<P>>` (one `Vec` per alpha, holding one packed value per point) is exactly what a
+/// single-threaded, whole-coset evaluation would have produced.
+pub fn eval_coset_parallel<P, F>(
+    alphas: &[P::Scalar],
+    num_points: usize,
+    lagrange_basis_first: &[P],
+    lagrange_basis_last: &[P],
+    z_last: &[P],
+    eval_point: F,
+) -> Vec<Vec<P>>
+where
+    P: PackedField,
+    F: Fn(usize, &mut ConstraintConsumer<P>) + Sync,
+{
+    assert_eq!(lagrange_basis_first.len(), num_points);
+    assert_eq!(lagrange_basis_last.len(), num_points);
+    assert_eq!(z_last.len(), num_points);
+
+    let size = chunk_size(num_points, rayon::current_num_threads());
+    let num_chunks = (num_points + size - 1) / size;
+
+    let chunked_accs: Vec<Vec<Vec<P>>> = (0..num_chunks)
+        .into_par_iter()
+        .map(|chunk_idx| {
+            let start = chunk_idx * size;
+            let end = (start + size).min(num_points);
+            (start..end)
+                .map(|i| {
+                    let mut consumer = ConstraintConsumer::new(
+                        alphas.to_vec(),
+                        lagrange_basis_first[i],
+                        lagrange_basis_last[i],
+                        z_last[i],
+                    );
+                    eval_point(i, &mut consumer);
+                    consumer.accumulators()
+                })
+                .collect()
+        })
+        .collect();
+
+    // Transpose from per-point `Vec<P>` (one entry per alpha) into per-alpha `Vec<P>` (one entry
+    // per point, in coset order), so callers get one packed accumulator stream per alpha.
+    let num_alphas = alphas.len();
+    let mut result = vec![Vec::with_capacity(num_points); num_alphas];
+    for chunk in chunked_accs {
+        for point_accs in chunk {
+            for (alpha_idx, acc) in point_accs.into_iter().enumerate() {
+                result[alpha_idx].push(acc);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::field_types::Field;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn chunk_size_never_leaves_an_oversized_tail() {
+        for num_points in [1, 2, 3, 16, 17, 100, 1023, 1024] {
+            for num_threads in [1, 2, 3, 8] {
+                let size = chunk_size(num_points, num_threads);
+                let num_chunks = (num_points + size - 1) / size;
+                assert!((num_chunks - 1) * size < num_points || num_points == 0);
+                assert!(num_chunks * size >= num_points);
+            }
+        }
+    }
+
+    #[test]
+    fn eval_coset_parallel_matches_sequential() {
+        let alphas = vec![F::from_canonical_u64(7)];
+        let num_points = 37;
+        let lagrange_basis_first = vec![F::ONE; num_points];
+        let lagrange_basis_last = vec![F::ONE; num_points];
+        let z_last = vec![F::ONE; num_points];
+
+        let result = eval_coset_parallel::<F, _>(
+            &alphas,
+            num_points,
+            &lagrange_basis_first,
+            &lagrange_basis_last,
+            &z_last,
+            |i, consumer| consumer.one(F::from_canonical_usize(i)),
+        );
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].len(), num_points);
+        for (i, &acc) in result[0].iter().enumerate() {
+            assert_eq!(acc, F::from_canonical_usize(i));
+        }
+    }
+}
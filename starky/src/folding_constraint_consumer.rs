@@ -0,0 +1,353 @@
+//! Support for Protostar/Sangria-style folding (accumulation) of STARK constraints.
+//!
+//! Unlike [`crate::constraint_consumer::ConstraintConsumer`], which immediately combines every
+//! emitted constraint into a handful of alpha-powered running sums, [`FoldingConstraintConsumer`]
+//! keeps every individual constraint evaluation around and homogenizes it to a common degree, so
+//! the folding layer ([`fold_error_terms`]) can later interpolate each constraint's error term
+//! across the witness/slack line.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use plonky2::field::field_types::Field;
+use plonky2::field::packed_field::PackedField;
+
+/// A value built up from constraint-evaluation arithmetic (`+`, `-`, `*`) that, instead of
+/// collapsing into a single evaluated scalar, keeps the accumulated value of every monomial
+/// degree separately. `terms[k]` holds the sum of every degree-`k` monomial produced so far (or
+/// `None` if none has been), so `Add`/`Sub` merge same-degree terms while `Mul` takes the cross
+/// product of degrees. This is what lets [`FoldingConstraintConsumer`] homogenize a constraint
+/// correctly: a constraint like the boolean check `b - b^2` mixes a degree-1 and a degree-2
+/// monomial, and each must be scaled by its own power of the slack variable, not by one power
+/// chosen for the constraint as a whole.
+#[derive(Clone, Debug)]
+pub struct HomogeneousValue<P: PackedField> {
+    terms: Vec<Option<P>>,
+}
+
+impl<P: PackedField> HomogeneousValue<P> {
+    /// A degree-0 value, e.g. a literal constant appearing in a constraint.
+    pub fn constant(value: P) -> Self {
+        Self::monomial(0, value)
+    }
+
+    /// A degree-1 value, e.g. a trace wire or one of the Lagrange/`z_last` filters.
+    pub fn wire(value: P) -> Self {
+        Self::monomial(1, value)
+    }
+
+    fn monomial(degree: usize, value: P) -> Self {
+        let mut terms = vec![None; degree + 1];
+        terms[degree] = Some(value);
+        Self { terms }
+    }
+
+    fn zero() -> Self {
+        Self { terms: Vec::new() }
+    }
+
+    /// The highest degree with a nonzero-known term, i.e. this value's total degree.
+    fn degree(&self) -> usize {
+        self.terms.len().saturating_sub(1)
+    }
+
+    /// Multiplies each degree-`k` term by `u^(degree - k)`, bringing the whole value up to a
+    /// single homogeneous degree, and sums the results.
+    fn homogenize(self, degree: usize, u: P::Scalar) -> P {
+        assert!(
+            self.degree() <= degree,
+            "constraint of degree {} exceeds the folding degree bound {}",
+            self.degree(),
+            degree
+        );
+        self.terms
+            .into_iter()
+            .enumerate()
+            .filter_map(|(k, term)| term.map(|v| v * u.exp_u64((degree - k) as u64)))
+            .fold(P::ZEROS, |acc, term| acc + term)
+    }
+}
+
+impl<P: PackedField> Add for HomogeneousValue<P> {
+    type Output = Self;
+    fn add(mut self, rhs: Self) -> Self {
+        if rhs.terms.len() > self.terms.len() {
+            self.terms.resize(rhs.terms.len(), None);
+        }
+        for (k, term) in rhs.terms.into_iter().enumerate() {
+            if let Some(v) = term {
+                self.terms[k] = Some(match self.terms[k] {
+                    Some(existing) => existing + v,
+                    None => v,
+                });
+            }
+        }
+        self
+    }
+}
+
+impl<P: PackedField> Sub for HomogeneousValue<P> {
+    type Output = Self;
+    fn sub(mut self, rhs: Self) -> Self {
+        if rhs.terms.len() > self.terms.len() {
+            self.terms.resize(rhs.terms.len(), None);
+        }
+        for (k, term) in rhs.terms.into_iter().enumerate() {
+            if let Some(v) = term {
+                self.terms[k] = Some(match self.terms[k] {
+                    Some(existing) => existing - v,
+                    None => P::ZEROS - v,
+                });
+            }
+        }
+        self
+    }
+}
+
+impl<P: PackedField> Neg for HomogeneousValue<P> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            terms: self
+                .terms
+                .into_iter()
+                .map(|term| term.map(|v| P::ZEROS - v))
+                .collect(),
+        }
+    }
+}
+
+impl<P: PackedField> Mul for HomogeneousValue<P> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        if self.terms.is_empty() || rhs.terms.is_empty() {
+            return Self::zero();
+        }
+        let max_degree = self.degree() + rhs.degree();
+        let mut terms = vec![None; max_degree + 1];
+        for (d1, t1) in self.terms.iter().enumerate() {
+            if let Some(v1) = t1 {
+                for (d2, t2) in rhs.terms.iter().enumerate() {
+                    if let Some(v2) = t2 {
+                        let product = *v1 * *v2;
+                        terms[d1 + d2] = Some(match terms[d1 + d2] {
+                            Some(existing) => existing + product,
+                            None => product,
+                        });
+                    }
+                }
+            }
+        }
+        Self { terms }
+    }
+}
+
+/// Like [`crate::constraint_consumer::ConstraintConsumer`], but instead of collapsing constraints
+/// into alpha-powered running sums, records the ordered evaluation of every individual
+/// constraint, each homogenized to degree `degree` by scaling its monomials with per-degree
+/// powers of the slack variable `u` (see [`HomogeneousValue`]). This is the per-evaluation
+/// building block a Protostar/Sangria-style folding scheme samples at several points of the
+/// folding challenge to recover error-term coefficients; see [`fold_error_terms`].
+pub struct FoldingConstraintConsumer<P: PackedField> {
+    /// The common degree every recorded constraint is homogenized to.
+    degree: usize,
+
+    /// The slack variable used to homogenize constraints of degree less than `degree`.
+    u: P::Scalar,
+
+    /// Every individual constraint evaluation recorded so far, in emission order, each already
+    /// homogenized to degree `degree`.
+    constraints: Vec<P>,
+
+    /// The evaluation of the Lagrange basis polynomial which is nonzero at the point associated
+    /// with the first trace row, and zero at other points in the subgroup.
+    lagrange_basis_first: HomogeneousValue<P>,
+
+    /// The evaluation of the Lagrange basis polynomial which is nonzero at the point associated
+    /// with the last trace row, and zero at other points in the subgroup.
+    lagrange_basis_last: HomogeneousValue<P>,
+
+    /// The evaluation, at the current point, of the vanishing polynomial of the last trace
+    /// point; see `ConstraintConsumer::z_last`.
+    z_last: HomogeneousValue<P>,
+}
+
+impl<P: PackedField> FoldingConstraintConsumer<P> {
+    pub fn new(
+        degree: usize,
+        u: P::Scalar,
+        lagrange_basis_first: P,
+        lagrange_basis_last: P,
+        z_last: P,
+    ) -> Self {
+        Self {
+            degree,
+            u,
+            constraints: Vec::new(),
+            lagrange_basis_first: HomogeneousValue::wire(lagrange_basis_first),
+            lagrange_basis_last: HomogeneousValue::wire(lagrange_basis_last),
+            z_last: HomogeneousValue::wire(z_last),
+        }
+    }
+
+    fn push(&mut self, constraint: HomogeneousValue<P>) {
+        self.constraints
+            .push(constraint.homogenize(self.degree, self.u));
+    }
+
+    /// Record one constraint, built up out of `HomogeneousValue` arithmetic.
+    pub fn one(&mut self, constraint: HomogeneousValue<P>) {
+        self.push(constraint);
+    }
+
+    /// Record a series of constraints.
+    pub fn many(&mut self, constraints: impl IntoIterator<Item = HomogeneousValue<P>>) {
+        constraints
+            .into_iter()
+            .for_each(|constraint| self.one(constraint));
+    }
+
+    /// Record one constraint, filtered to the first row of the trace.
+    pub fn one_first_row(&mut self, constraint: HomogeneousValue<P>) {
+        self.push(constraint * self.lagrange_basis_first.clone());
+    }
+
+    /// Record one constraint, filtered to the last row of the trace.
+    pub fn one_last_row(&mut self, constraint: HomogeneousValue<P>) {
+        self.push(constraint * self.lagrange_basis_last.clone());
+    }
+
+    /// Record one transition constraint, filtered to every row but the last. See
+    /// `ConstraintConsumer::one_transition`.
+    pub fn one_transition(&mut self, constraint: HomogeneousValue<P>) {
+        self.push(constraint * self.z_last.clone());
+    }
+
+    /// Consumes the consumer, returning every recorded constraint evaluation in emission order.
+    pub fn into_constraints(self) -> Vec<P> {
+        self.constraints
+    }
+}
+
+/// Runs a folding-homogenized constraint evaluation at the `degree + 1` points `X = 0, 1, ...,
+/// degree`, where `eval_at(x)` is expected to evaluate the STARK's constraints on the witness
+/// line `w(X) = w_acc + X * w_new` and slack line `u(X) = u_acc + X` at `X = x`, returning the
+/// resulting [`FoldingConstraintConsumer`]'s recorded constraint vector (via
+/// `FoldingConstraintConsumer::into_constraints`).
+///
+/// For each constraint (in emission order), the `degree + 1` sampled values are the evaluations,
+/// at `X = 0, ..., degree`, of the unique degree-`degree` univariate that the folding scheme
+/// cares about: `X^0` is the running accumulator's error term for that constraint, `X^degree` is
+/// the fresh instance's (zero for a satisfying witness), and `X^1, ..., X^(degree - 1)` are the
+/// cross terms the accumulator's error vector needs updating by. This function interpolates that
+/// univariate from the samples and returns, per constraint, the full coefficient vector
+/// `[c_0, ..., c_degree]` so the caller can pick out whichever coefficients it needs.
+pub fn fold_error_terms<P: PackedField>(
+    degree: usize,
+    eval_at: impl Fn(usize) -> Vec<P>,
+) -> Vec<Vec<P>> {
+    let num_points = degree + 1;
+    let samples: Vec<Vec<P>> = (0..num_points).map(eval_at).collect();
+    let num_constraints = samples.first().map_or(0, Vec::len);
+    for sample in &samples {
+        assert_eq!(
+            sample.len(),
+            num_constraints,
+            "every sample must record the same constraints"
+        );
+    }
+
+    let xs: Vec<P::Scalar> = (0..num_points as u64)
+        .map(P::Scalar::from_canonical_u64)
+        .collect();
+
+    (0..num_constraints)
+        .map(|constraint_idx| {
+            let ys: Vec<P> = samples
+                .iter()
+                .map(|sample| sample[constraint_idx])
+                .collect();
+            interpolate_coeffs(&xs, &ys)
+        })
+        .collect()
+}
+
+/// Returns the coefficients `[c_0, ..., c_{n-1}]` of the unique degree-`(n - 1)` polynomial
+/// passing through `(xs[i], ys[i])` for all `i`, via Lagrange interpolation.
+fn interpolate_coeffs<P: PackedField>(xs: &[P::Scalar], ys: &[P]) -> Vec<P> {
+    let n = xs.len();
+    let mut coeffs = vec![P::ZEROS; n];
+    for j in 0..n {
+        // The Lagrange basis polynomial `L_j(X) = prod_{m != j} (X - x_m) / (x_j - x_m)`,
+        // represented as coefficients from low to high degree.
+        let mut numerator = vec![P::Scalar::ONE];
+        let mut denominator = P::Scalar::ONE;
+        for (m, &x_m) in xs.iter().enumerate() {
+            if m == j {
+                continue;
+            }
+            denominator *= xs[j] - x_m;
+            let mut shifted = vec![P::Scalar::ZERO; numerator.len() + 1];
+            for (k, &c) in numerator.iter().enumerate() {
+                shifted[k + 1] += c;
+                shifted[k] -= c * x_m;
+            }
+            numerator = shifted;
+        }
+        let scale = denominator.inverse();
+        for (k, &c) in numerator.iter().enumerate() {
+            coeffs[k] += ys[j] * (c * scale);
+        }
+    }
+    coeffs
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::field_types::Field;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn interpolates_a_known_polynomial() {
+        // f(X) = 3 + 2X + 5X^2
+        let f = |x: u64| F::from_canonical_u64(3 + 2 * x + 5 * x * x);
+        let xs: Vec<F> = (0..3u64).map(F::from_canonical_u64).collect();
+        let ys: Vec<F> = (0..3u64).map(f).collect();
+
+        let coeffs = interpolate_coeffs::<F>(&xs, &ys);
+
+        assert_eq!(
+            coeffs,
+            vec![
+                F::from_canonical_u64(3),
+                F::from_canonical_u64(2),
+                F::from_canonical_u64(5),
+            ]
+        );
+    }
+
+    #[test]
+    fn homogenizes_a_mixed_degree_constraint_per_monomial() {
+        let u = F::from_canonical_u64(7);
+        let b = F::from_canonical_u64(5);
+        let mut consumer = FoldingConstraintConsumer::<F>::new(2, u, F::ONE, F::ONE, F::ONE);
+
+        // The boolean check `b * (1 - b) = b - b^2` mixes a degree-1 monomial (`b`) and a
+        // degree-2 monomial (`-b^2`).
+        let b_term = HomogeneousValue::wire(b);
+        let one_term = HomogeneousValue::constant(F::ONE);
+        consumer.one(b_term.clone() * (one_term - b_term));
+
+        let recorded = consumer.into_constraints();
+
+        // Homogenizing to degree 2 scales the degree-1 monomial by `u^1` and leaves the
+        // already-degree-2 monomial alone (`u^0`); collapsing to a single scalar first and then
+        // multiplying by one slack power would instead (and incorrectly) compute `u * (b - b^2)`.
+        let expected = u * b - b * b;
+        assert_eq!(recorded, vec![expected]);
+        assert_ne!(recorded, vec![u * (b - b * b)]);
+    }
+}